@@ -8,10 +8,11 @@
 //!
 //! This crate provides an abstraction layer for storing and loading
 //! data in different environments: File I/O on desktop devices and
-//! local storage when running in the browser.  The goal behind this
-//! crate is to allow for a single initialization-phase configuration,
-//! following which the API can be used throughout the application
-//! without the concern about the operating environment.
+//! IndexedDB (with a `localStorage` fallback) when running in the
+//! browser.  The goal behind this crate is to allow for a single
+//! initialization-phase configuration, following which the API can be
+//! used throughout the application without the concern about the
+//! operating environment.
 //!
 //!
 
@@ -19,16 +20,33 @@ pub mod error;
 pub mod result;
 
 use crate::result::Result;
+use async_trait::async_trait;
 use cfg_if::cfg_if;
 use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
 
+///
+/// Symbolic storage root used by [`Store::with_data_dir`] and
+/// [`Store::with_config_dir`] to resolve a conventional, per-platform
+/// location instead of a hard-coded absolute path.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BaseDir {
+    // per-user application data (`%APPDATA%`, `~/Library/Application Support`, `$XDG_DATA_HOME`)
+    Data,
+    // per-user application config (`%APPDATA%`, `~/Library/Application Support`, `$XDG_CONFIG_HOME`)
+    Config,
+    // per-user application state (`$XDG_STATE_HOME`); mirrors `Data` off-Linux
+    State,
+}
+
 cfg_if! {
     if #[cfg(not(target_arch = "wasm32"))] {
         use async_std::path::PathBuf;
         use async_std::fs;
     } else {
         use base64::{encode, decode};
+        use wasm_bindgen::JsCast;
     }
 }
 
@@ -52,6 +70,17 @@ pub struct Store {
     pub generic: Option<String>,
     // browser locastorage (fallsback to a hash of generic in hex)
     pub browser: Option<String>,
+    // symbolic storage root; when set, the resolved filename is joined
+    // beneath the platform data/config directory under `app`
+    pub base: Option<BaseDir>,
+    // application name used as the sub-directory beneath `base`
+    pub app: Option<String>,
+    // storage backend; when `None` the default cfg-selected backend is used
+    pub backend: Option<Box<dyn Backend>>,
+    // write via a temp file + atomic rename (filesystem backend; default true)
+    pub atomic: bool,
+    // retain the previous contents as a sibling `.bak` before each write
+    pub backup: bool,
 }
 
 impl Default for Store {
@@ -69,6 +98,11 @@ impl Store {
             windows: None,
             generic: None,
             browser: None,
+            base: None,
+            app: None,
+            backend: None,
+            atomic: true,
+            backup: false,
         }
     }
 
@@ -102,6 +136,37 @@ impl Store {
         self
     }
 
+    /// Resolve storage beneath the platform application *data* directory,
+    /// placing files under `<data root>/<app_name>/`. The data root is
+    /// `%APPDATA%` on Windows (falling back to
+    /// `%USERPROFILE%\AppData\Roaming`), `$HOME/Library/Application Support`
+    /// on macOS, and `$XDG_DATA_HOME` or `$HOME/.local/share` on Linux.
+    pub fn with_data_dir(&mut self, app_name: &str) -> &mut Store {
+        self.base = Some(BaseDir::Data);
+        self.app = Some(app_name.to_string());
+        self
+    }
+
+    /// Resolve storage beneath the platform application *config* directory,
+    /// placing files under `<config root>/<app_name>/`. The config root is
+    /// `%APPDATA%` on Windows, `$HOME/Library/Application Support` on macOS,
+    /// and `$XDG_CONFIG_HOME` or `$HOME/.config` on Linux.
+    pub fn with_config_dir(&mut self, app_name: &str) -> &mut Store {
+        self.base = Some(BaseDir::Config);
+        self.app = Some(app_name.to_string());
+        self
+    }
+
+    /// Resolve storage beneath the platform application *state* directory,
+    /// placing files under `<state root>/<app_name>/`. On Linux the state
+    /// root is `$XDG_STATE_HOME` or `$HOME/.local/state`; on other platforms
+    /// it mirrors the data directory.
+    pub fn with_state_dir(&mut self, app_name: &str) -> &mut Store {
+        self.base = Some(BaseDir::State);
+        self.app = Some(app_name.to_string());
+        self
+    }
+
     pub fn filename(&self) -> String {
         cfg_if! {
             if #[cfg(target_os = "macos")] {
@@ -125,43 +190,116 @@ impl Store {
         }
     }
 
-    cfg_if! {
-        if #[cfg(target_arch = "wasm32")] {
-            pub async fn exists(&self) -> Result<bool> {
-                let filename = self.filename();
-                Ok(local_storage().get_item(&filename)?.is_some())
-            }
+    /// Install a custom storage [`Backend`], overriding the default
+    /// cfg-selected backend (filesystem on native, IndexedDB in the
+    /// browser). Useful for injecting a [`MemoryBackend`] in tests.
+    pub fn with_backend(&mut self, backend: impl Backend + 'static) -> &mut Store {
+        self.backend = Some(Box::new(backend));
+        self
+    }
 
-            pub async fn read(&self) -> Result<Vec<u8>> {
-                let filename = self.filename();
-                let v = local_storage().get_item(&filename)?.unwrap();
-                Ok(decode(v)?)
-            }
+    /// Select the browser persistence layer, overriding the default
+    /// ([`BrowserBackend::IndexedDb`]). Use [`BrowserBackend::LocalStorage`]
+    /// to fall back to the base64 `localStorage` store for small configs.
+    #[cfg(target_arch = "wasm32")]
+    pub fn with_browser_backend(&mut self, backend: BrowserBackend) -> &mut Store {
+        self.backend = Some(match backend {
+            BrowserBackend::LocalStorage => Box::new(LocalStorageBackend),
+            BrowserBackend::IndexedDb => Box::new(IndexedDbBackend::default()),
+        });
+        self
+    }
 
-            pub async fn write(&self, data: &[u8]) -> Result<()> {
-                let filename = self.filename();
-                let v = encode(data);
-                local_storage().set_item(&filename, &v)?;
-                Ok(())
-            }
+    /// Write atomically via a sibling temporary file and `rename` (the
+    /// default). When disabled, the filesystem backend truncates and writes
+    /// the target in place. Only affects the built-in [`FilesystemBackend`]
+    /// used when no backend has been injected via [`Store::with_backend`] —
+    /// a no-op for browser backends and for any custom backend.
+    pub fn with_atomic(&mut self, atomic: bool) -> &mut Store {
+        self.atomic = atomic;
+        self
+    }
 
+    /// Retain the previous file contents as a sibling `.bak` before each
+    /// write. Only affects the built-in [`FilesystemBackend`] used when no
+    /// backend has been injected via [`Store::with_backend`] — a no-op for
+    /// browser backends and for any custom backend.
+    pub fn with_backup(&mut self, backup: bool) -> &mut Store {
+        self.backup = backup;
+        self
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    /// Absolute path the store reads from and writes to. When a [`BaseDir`]
+    /// has been configured via [`Store::with_data_dir`],
+    /// [`Store::with_config_dir`] or [`Store::with_state_dir`], the resolved
+    /// filename is joined beneath the platform directory under `app`;
+    /// otherwise the filename is parsed directly (expanding a leading `~`).
+    pub fn path(&self) -> PathBuf {
+        if let Some(base) = self.base {
+            let root = match base {
+                BaseDir::Data => data_dir(),
+                BaseDir::Config => config_dir(),
+                BaseDir::State => state_dir(),
+            };
+            let app = self.app.as_ref().expect("app name is required for a base directory");
+            root.join(app).join(self.filename())
         } else {
-            pub async fn exists(&self) -> Result<bool> {
-                let filename = parse(self.filename());
-                Ok(filename.exists().await)
-            }
+            parse(self.filename())
+        }
+    }
 
-            pub async fn read(&self) -> Result<Vec<u8>> {
-                let filename = parse(self.filename());
-                Ok(fs::read(&filename).await?)
+    // Backend key for the current environment: the resolved absolute path on
+    // native platforms, the `localStorage`/IndexedDB key in the browser.
+    fn key(&self) -> String {
+        cfg_if! {
+            if #[cfg(target_arch = "wasm32")] {
+                self.filename()
+            } else {
+                self.path().to_string_lossy().to_string()
             }
+        }
+    }
 
-            pub async fn write(&self, data: &[u8]) -> Result<()> {
-                let filename = parse(self.filename());
-                Ok(fs::write(&filename, data).await?)
+    // Default backend carrying this store's atomic/backup configuration,
+    // used when no backend has been injected via [`Store::with_backend`].
+    fn default_backend(&self) -> Box<dyn Backend> {
+        cfg_if! {
+            if #[cfg(target_arch = "wasm32")] {
+                Box::new(IndexedDbBackend::default())
+            } else {
+                Box::new(FilesystemBackend { atomic: self.atomic, backup: self.backup })
             }
         }
     }
+
+    pub async fn exists(&self) -> Result<bool> {
+        match &self.backend {
+            Some(backend) => backend.exists(&self.key()).await,
+            None => self.default_backend().exists(&self.key()).await,
+        }
+    }
+
+    pub async fn read(&self) -> Result<Vec<u8>> {
+        match &self.backend {
+            Some(backend) => backend.read(&self.key()).await,
+            None => self.default_backend().read(&self.key()).await,
+        }
+    }
+
+    pub async fn write(&self, data: &[u8]) -> Result<()> {
+        match &self.backend {
+            Some(backend) => backend.write(&self.key(), data).await,
+            None => self.default_backend().write(&self.key(), data).await,
+        }
+    }
+
+    pub async fn remove(&self) -> Result<()> {
+        match &self.backend {
+            Some(backend) => backend.remove(&self.key()).await,
+            None => self.default_backend().remove(&self.key()).await,
+        }
+    }
 }
 
 cfg_if! {
@@ -175,6 +313,94 @@ cfg_if! {
                 PathBuf::from(path)
             }
         }
+
+        // Unique temp path next to `path` for atomic writes. The suffix
+        // combines the process id with a monotonic counter so concurrent
+        // writers within a process never collide.
+        fn temp_sibling(path: &str) -> String {
+            use std::sync::atomic::{AtomicU64, Ordering};
+            static COUNTER: AtomicU64 = AtomicU64::new(0);
+            let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+            format!("{path}.tmp-{}-{n}", std::process::id())
+        }
+
+        // absolute value of `var` or `None` when unset, empty or relative
+        fn env_abs(var: &str) -> Option<PathBuf> {
+            std::env::var_os(var)
+                .map(PathBuf::from)
+                .filter(|p| p.is_absolute())
+        }
+
+        fn home_dir() -> PathBuf {
+            home::home_dir().unwrap().into()
+        }
+
+        // On Linux (and other Unix targets reached via the same fallback
+        // arm below, e.g. the BSDs) the home used for the XDG `~/.local`
+        // fallbacks is the sandbox-provided data root when running under
+        // Snap, so packaged binaries write inside their confinement rather
+        // than the host home.
+        #[cfg(target_family = "unix")]
+        fn xdg_home() -> PathBuf {
+            env_abs("SNAP_USER_DATA").unwrap_or_else(home_dir)
+        }
+
+        /// `true` when running inside a Flatpak sandbox.
+        pub fn is_flatpak() -> bool {
+            std::path::Path::new("/.flatpak-info").exists()
+        }
+
+        /// `true` when running inside a Snap confinement.
+        pub fn is_snap() -> bool {
+            std::env::var_os("SNAP").is_some() || std::env::var_os("SNAP_USER_DATA").is_some()
+        }
+
+        /// `true` when running from an AppImage mount.
+        pub fn is_appimage() -> bool {
+            std::env::var_os("APPIMAGE").is_some() || std::env::var_os("APPDIR").is_some()
+        }
+
+        /// Platform application data root (see [`Store::with_data_dir`]).
+        pub fn data_dir() -> PathBuf {
+            cfg_if! {
+                if #[cfg(target_os = "macos")] {
+                    home_dir().join("Library").join("Application Support")
+                } else if #[cfg(target_family = "windows")] {
+                    env_abs("APPDATA")
+                        .unwrap_or_else(|| home_dir().join("AppData").join("Roaming"))
+                } else {
+                    env_abs("XDG_DATA_HOME")
+                        .unwrap_or_else(|| xdg_home().join(".local").join("share"))
+                }
+            }
+        }
+
+        /// Platform application config root (see [`Store::with_config_dir`]).
+        pub fn config_dir() -> PathBuf {
+            cfg_if! {
+                if #[cfg(target_os = "macos")] {
+                    home_dir().join("Library").join("Application Support")
+                } else if #[cfg(target_family = "windows")] {
+                    env_abs("APPDATA")
+                        .unwrap_or_else(|| home_dir().join("AppData").join("Roaming"))
+                } else {
+                    env_abs("XDG_CONFIG_HOME")
+                        .unwrap_or_else(|| xdg_home().join(".config"))
+                }
+            }
+        }
+
+        /// Platform application state root (see [`Store::with_state_dir`]).
+        pub fn state_dir() -> PathBuf {
+            cfg_if! {
+                if #[cfg(target_os = "linux")] {
+                    env_abs("XDG_STATE_HOME")
+                        .unwrap_or_else(|| xdg_home().join(".local").join("state"))
+                } else {
+                    data_dir()
+                }
+            }
+        }
     } else {
         pub fn local_storage() -> web_sys::Storage {
             web_sys::window().unwrap().local_storage().unwrap().unwrap()
@@ -182,6 +408,450 @@ cfg_if! {
     }
 }
 
+///
+/// # Backend
+///
+/// Storage backend abstraction behind [`Store`]'s read/write/exists/remove
+/// API. The `key` is an opaque, environment-specific identifier — the
+/// resolved absolute path on native platforms and the `localStorage`/IndexedDB
+/// key in the browser. Implement this trait to inject custom persistence via
+/// [`Store::with_backend`].
+///
+#[async_trait(?Send)]
+pub trait Backend {
+    async fn read(&self, key: &str) -> Result<Vec<u8>>;
+    async fn write(&self, key: &str, data: &[u8]) -> Result<()>;
+    async fn exists(&self, key: &str) -> Result<bool>;
+    async fn remove(&self, key: &str) -> Result<()>;
+}
+
+/// The default backend for the current environment: [`FilesystemBackend`] on
+/// native platforms and [`IndexedDbBackend`] in the browser.
+pub fn default_backend() -> Box<dyn Backend> {
+    cfg_if! {
+        if #[cfg(target_arch = "wasm32")] {
+            Box::new(IndexedDbBackend::default())
+        } else {
+            Box::new(FilesystemBackend::default())
+        }
+    }
+}
+
+cfg_if! {
+    if #[cfg(not(target_arch = "wasm32"))] {
+        /// Filesystem backend. The `key` is an absolute path; `write`
+        /// auto-creates the destination's parent directories. With `atomic`
+        /// set (the default) writes go to a sibling temp file that is fsynced
+        /// and `rename`d over the target; with `backup` the previous contents
+        /// are kept as a sibling `.bak`.
+        pub struct FilesystemBackend {
+            pub atomic: bool,
+            pub backup: bool,
+        }
+
+        impl Default for FilesystemBackend {
+            fn default() -> Self {
+                FilesystemBackend { atomic: true, backup: false }
+            }
+        }
+
+        #[async_trait(?Send)]
+        impl Backend for FilesystemBackend {
+            async fn read(&self, key: &str) -> Result<Vec<u8>> {
+                Ok(fs::read(key).await?)
+            }
+
+            async fn write(&self, key: &str, data: &[u8]) -> Result<()> {
+                let path = PathBuf::from(key);
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent).await?;
+                }
+
+                // Copy the previous contents aside before anything touches the
+                // destination, so `backup` behaves the same whether or not
+                // `atomic` is also enabled.
+                if self.backup && path.exists().await {
+                    let bak = PathBuf::from(format!("{key}.bak"));
+                    fs::copy(&path, &bak).await?;
+                }
+
+                if !self.atomic {
+                    return Ok(fs::write(&path, data).await?);
+                }
+
+                // Write to a uniquely-named sibling in the *same* directory so
+                // the subsequent `rename` stays on one filesystem (and is
+                // therefore atomic), fsyncing before we swap it in.
+                use async_std::io::WriteExt;
+                let tmp = PathBuf::from(temp_sibling(key));
+                {
+                    let mut file = fs::File::create(&tmp).await?;
+                    file.write_all(data).await?;
+                    file.flush().await?;
+                    file.sync_all().await?;
+                }
+
+                Ok(fs::rename(&tmp, &path).await?)
+            }
+
+            async fn exists(&self, key: &str) -> Result<bool> {
+                Ok(PathBuf::from(key).exists().await)
+            }
+
+            async fn remove(&self, key: &str) -> Result<()> {
+                Ok(fs::remove_file(key).await?)
+            }
+        }
+
+        #[cfg(test)]
+        mod filesystem_backend_tests {
+            use super::*;
+
+            // Unique scratch directory per test so concurrent `cargo test`
+            // threads never collide.
+            fn scratch_dir() -> std::path::PathBuf {
+                use std::sync::atomic::{AtomicU64, Ordering};
+                static COUNTER: AtomicU64 = AtomicU64::new(0);
+                let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+                let dir = std::env::temp_dir()
+                    .join(format!("workflow-store-test-{}-{n}", std::process::id()));
+                std::fs::create_dir_all(&dir).unwrap();
+                dir
+            }
+
+            // Write twice through `backend` and assert the `.bak` sibling is
+            // (or isn't) left behind matching `backup`, independent of
+            // `atomic`.
+            fn assert_atomic_backup_combo(atomic: bool, backup: bool) {
+                async_std::task::block_on(async {
+                    let dir = scratch_dir();
+                    let path = dir.join("file.txt");
+                    let key = path.to_string_lossy().to_string();
+                    let bak = format!("{key}.bak");
+                    let backend = FilesystemBackend { atomic, backup };
+
+                    backend.write(&key, b"first").await.unwrap();
+                    assert_eq!(backend.read(&key).await.unwrap(), b"first");
+
+                    backend.write(&key, b"second").await.unwrap();
+                    assert_eq!(backend.read(&key).await.unwrap(), b"second");
+
+                    if backup {
+                        assert_eq!(
+                            std::fs::read(&bak).unwrap(),
+                            b"first",
+                            "atomic={atomic} backup={backup}: .bak should hold the pre-write contents"
+                        );
+                    } else {
+                        assert!(
+                            !std::path::Path::new(&bak).exists(),
+                            "atomic={atomic} backup={backup}: no .bak should be written"
+                        );
+                    }
+
+                    std::fs::remove_dir_all(&dir).ok();
+                });
+            }
+
+            #[test]
+            fn atomic_true_backup_true() {
+                assert_atomic_backup_combo(true, true);
+            }
+
+            #[test]
+            fn atomic_true_backup_false() {
+                assert_atomic_backup_combo(true, false);
+            }
+
+            #[test]
+            fn atomic_false_backup_true() {
+                assert_atomic_backup_combo(false, true);
+            }
+
+            #[test]
+            fn atomic_false_backup_false() {
+                assert_atomic_backup_combo(false, false);
+            }
+        }
+    } else {
+        /// `localStorage` backend. Values are base64-encoded to survive the
+        /// string-only store.
+        pub struct LocalStorageBackend;
+
+        #[async_trait(?Send)]
+        impl Backend for LocalStorageBackend {
+            async fn read(&self, key: &str) -> Result<Vec<u8>> {
+                let v = local_storage().get_item(key)?.unwrap();
+                Ok(decode(v)?)
+            }
+
+            async fn write(&self, key: &str, data: &[u8]) -> Result<()> {
+                local_storage().set_item(key, &encode(data))?;
+                Ok(())
+            }
+
+            async fn exists(&self, key: &str) -> Result<bool> {
+                Ok(local_storage().get_item(key)?.is_some())
+            }
+
+            async fn remove(&self, key: &str) -> Result<()> {
+                local_storage().remove_item(key)?;
+                Ok(())
+            }
+        }
+
+        /// Browser persistence selector for [`Store::with_browser_backend`].
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub enum BrowserBackend {
+            // base64 in `localStorage` (small configs, ~5 MB origin quota)
+            LocalStorage,
+            // raw bytes in IndexedDB (large binary blobs)
+            IndexedDb,
+        }
+
+        ///
+        /// IndexedDB backend. Persists raw `Vec<u8>` values (no base64
+        /// inflation, no `localStorage` quota) keyed by [`Store::filename`].
+        /// A single database holds one object store; the store is created
+        /// lazily in the `onupgradeneeded` callback. The opened connection
+        /// is cached on the backend so repeated reads/writes don't each pay
+        /// a fresh database-open round trip.
+        ///
+        pub struct IndexedDbBackend {
+            pub database: String,
+            pub object_store: String,
+            connection: std::cell::RefCell<Option<web_sys::IdbDatabase>>,
+        }
+
+        impl Default for IndexedDbBackend {
+            fn default() -> Self {
+                IndexedDbBackend {
+                    database: "workflow-store".to_string(),
+                    object_store: "store".to_string(),
+                    connection: std::cell::RefCell::new(None),
+                }
+            }
+        }
+
+        impl IndexedDbBackend {
+            pub fn new(database: &str, object_store: &str) -> IndexedDbBackend {
+                IndexedDbBackend {
+                    database: database.to_string(),
+                    object_store: object_store.to_string(),
+                    connection: std::cell::RefCell::new(None),
+                }
+            }
+
+            // Return the cached connection, opening (and caching) it on
+            // first use.
+            async fn connection(&self) -> Result<web_sys::IdbDatabase> {
+                if let Some(db) = self.connection.borrow().as_ref() {
+                    return Ok(db.clone());
+                }
+                let db = self.open().await?;
+                *self.connection.borrow_mut() = Some(db.clone());
+                Ok(db)
+            }
+
+            // Open the database, creating the object store on first use.
+            async fn open(&self) -> Result<web_sys::IdbDatabase> {
+                let factory = web_sys::window().unwrap().indexed_db()?.unwrap();
+                let request = factory.open_with_u32(&self.database, 1)?;
+
+                let object_store = self.object_store.clone();
+                let on_upgrade = wasm_bindgen::closure::Closure::once_into_js(
+                    move |event: web_sys::Event| {
+                        let request: web_sys::IdbOpenDbRequest =
+                            event.target().unwrap().unchecked_into();
+                        let db: web_sys::IdbDatabase = request.result().unwrap().unchecked_into();
+                        if !db.object_store_names().contains(&object_store) {
+                            db.create_object_store(&object_store).unwrap();
+                        }
+                    },
+                );
+                request.set_onupgradeneeded(Some(on_upgrade.as_ref().unchecked_ref()));
+
+                let db = await_request(request.as_ref()).await?;
+                Ok(db.unchecked_into())
+            }
+
+            // Run `f` against the object store inside a transaction of `mode`.
+            async fn with_store<F>(
+                &self,
+                mode: web_sys::IdbTransactionMode,
+                f: F,
+            ) -> Result<wasm_bindgen::JsValue>
+            where
+                F: FnOnce(&web_sys::IdbObjectStore) -> Result<web_sys::IdbRequest>,
+            {
+                let db = self.connection().await?;
+                let tx = db
+                    .transaction_with_str_and_mode(&self.object_store, mode)?;
+                let store = tx.object_store(&self.object_store)?;
+                let request = f(&store)?;
+                await_request(request.as_ref()).await
+            }
+        }
+
+        #[async_trait(?Send)]
+        impl Backend for IndexedDbBackend {
+            async fn read(&self, key: &str) -> Result<Vec<u8>> {
+                let js_key = wasm_bindgen::JsValue::from_str(key);
+                let value = self
+                    .with_store(web_sys::IdbTransactionMode::Readonly, |store| {
+                        Ok(store.get(&js_key)?)
+                    })
+                    .await?;
+                // `get` resolves to `undefined` for a missing key rather than
+                // rejecting, and `Uint8Array::new(&undefined)` happily yields
+                // an empty array — distinguish "no such key" from "empty
+                // value" explicitly instead of silently returning `Ok(vec![])`.
+                if value.is_undefined() || value.is_null() {
+                    return Err(crate::error::Error::from(format!(
+                        "no such key: {key}"
+                    )));
+                }
+                Ok(js_sys::Uint8Array::new(&value).to_vec())
+            }
+
+            async fn write(&self, key: &str, data: &[u8]) -> Result<()> {
+                let key = wasm_bindgen::JsValue::from_str(key);
+                let value = js_sys::Uint8Array::from(data);
+                self.with_store(web_sys::IdbTransactionMode::Readwrite, |store| {
+                    Ok(store.put_with_key(value.as_ref(), &key)?)
+                })
+                .await?;
+                Ok(())
+            }
+
+            async fn exists(&self, key: &str) -> Result<bool> {
+                let key = wasm_bindgen::JsValue::from_str(key);
+                let value = self
+                    .with_store(web_sys::IdbTransactionMode::Readonly, |store| {
+                        Ok(store.get(&key)?)
+                    })
+                    .await?;
+                Ok(!value.is_undefined() && !value.is_null())
+            }
+
+            async fn remove(&self, key: &str) -> Result<()> {
+                let key = wasm_bindgen::JsValue::from_str(key);
+                self.with_store(web_sys::IdbTransactionMode::Readwrite, |store| {
+                    Ok(store.delete(&key)?)
+                })
+                .await?;
+                Ok(())
+            }
+        }
+
+        // Resolve an `IdbRequest`/`IdbOpenDbRequest` to its `result`, driving
+        // the request/transaction callbacks as a future.
+        async fn await_request(request: &web_sys::IdbRequest) -> Result<wasm_bindgen::JsValue> {
+            let (tx, rx) = futures::channel::oneshot::channel();
+            let tx = std::rc::Rc::new(std::cell::RefCell::new(Some(tx)));
+
+            let on_success = {
+                let tx = tx.clone();
+                wasm_bindgen::closure::Closure::once_into_js(move || {
+                    if let Some(tx) = tx.borrow_mut().take() {
+                        let _ = tx.send(Ok(()));
+                    }
+                })
+            };
+            let on_error = {
+                let tx = tx.clone();
+                wasm_bindgen::closure::Closure::once_into_js(move || {
+                    if let Some(tx) = tx.borrow_mut().take() {
+                        let _ = tx.send(Err(()));
+                    }
+                })
+            };
+            request.set_onsuccess(Some(on_success.as_ref().unchecked_ref()));
+            request.set_onerror(Some(on_error.as_ref().unchecked_ref()));
+
+            match rx.await {
+                Ok(Ok(())) => Ok(request.result()?),
+                _ => Err(request
+                    .error()?
+                    .map(|e| crate::error::Error::from(wasm_bindgen::JsValue::from(e)))
+                    .unwrap_or_else(|| crate::error::Error::from("IndexedDB request failed"))),
+            }
+        }
+    }
+}
+
+///
+/// In-memory [`Backend`] backed by a `HashMap<String, Vec<u8>>`. It ignores
+/// the host environment entirely, giving each instance an isolated root, so
+/// persistence logic — including the wasm codepaths — can be exercised
+/// deterministically in tests.
+///
+#[derive(Default)]
+pub struct MemoryBackend {
+    map: std::sync::Mutex<std::collections::HashMap<String, Vec<u8>>>,
+}
+
+impl MemoryBackend {
+    pub fn new() -> MemoryBackend {
+        MemoryBackend::default()
+    }
+}
+
+#[async_trait(?Send)]
+impl Backend for MemoryBackend {
+    async fn read(&self, key: &str) -> Result<Vec<u8>> {
+        let map = self.map.lock().unwrap();
+        map.get(key)
+            .cloned()
+            .ok_or_else(|| crate::error::Error::from(format!("no such key: {key}")))
+    }
+
+    async fn write(&self, key: &str, data: &[u8]) -> Result<()> {
+        let mut map = self.map.lock().unwrap();
+        map.insert(key.to_string(), data.to_vec());
+        Ok(())
+    }
+
+    async fn exists(&self, key: &str) -> Result<bool> {
+        let map = self.map.lock().unwrap();
+        Ok(map.contains_key(key))
+    }
+
+    async fn remove(&self, key: &str) -> Result<()> {
+        let mut map = self.map.lock().unwrap();
+        map.remove(key);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod memory_backend_tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_write_read_exists_remove() {
+        async_std::task::block_on(async {
+            let backend = MemoryBackend::new();
+            assert!(!backend.exists("a").await.unwrap());
+
+            backend.write("a", b"hello").await.unwrap();
+            assert!(backend.exists("a").await.unwrap());
+            assert_eq!(backend.read("a").await.unwrap(), b"hello");
+
+            backend.remove("a").await.unwrap();
+            assert!(!backend.exists("a").await.unwrap());
+        });
+    }
+
+    #[test]
+    fn read_missing_key_errors_instead_of_panicking() {
+        async_std::task::block_on(async {
+            let backend = MemoryBackend::new();
+            assert!(backend.read("missing").await.is_err());
+        });
+    }
+}
+
 pub fn find(paths: &[Option<&String>]) -> String {
     for path in paths.iter() {
         if let Some(path) = *path {